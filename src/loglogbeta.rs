@@ -1,10 +1,34 @@
 extern crate siphasher;
 
 use std::cmp::{max};
-use std::hash::{Hash,Hasher};
+use std::hash::{BuildHasher, BuildHasherDefault, Hash};
 use self::siphasher::sip::SipHasher;
 use std::marker::PhantomData;
 
+const REGISTER_BITS: usize = 6;
+const REGISTER_MASK: u16 = (1 << REGISTER_BITS) - 1;
+
+/// Header tag identifying the `to_bytes`/`from_bytes` wire format.
+const FORMAT_MAGIC: &[u8; 3] = b"LLB";
+const FORMAT_VERSION: u8 = 1;
+
+const MODE_DENSE: u8 = 0;
+const MODE_SPARSE: u8 = 1;
+
+/// Sparse entries pack a register index and its `rho` value into a single `u32`:
+/// the low 6 bits hold `rho`, the remaining bits hold the index.
+const SPARSE_RHO_BITS: u32 = REGISTER_BITS as u32;
+
+/// Backing storage for a counter's registers.
+///
+/// Counters start life `Sparse`, storing only the registers that have actually been touched,
+/// and are promoted to `Dense` once the sparse list would cost more memory than the packed
+/// dense array.
+enum Registers {
+    Sparse(Vec<u32>),
+    Dense(Vec<u8>),
+}
+
 /// [LogLog-Beta and More: A New Algorithm for Cardinality Estimation Based on LogLog Counting](https://arxiv.org/abs/1612.02284)
 ///
 /// A new algorithm for estimating cardinalities. More efficient and easier to implement than
@@ -23,62 +47,400 @@ use std::marker::PhantomData;
 /// assert!(hll.estimate() < 10500.0);
 /// assert!(hll.estimate() >  9500.0);
 /// ```
-///
-
-pub struct LogLogBeta<E> {
+pub struct LogLogBeta<E, S = BuildHasherDefault<SipHasher>> {
     alpha: f64,
     p: usize,
     msize: u64,
-    m: Vec<u64>,
+    registers: Registers,
+    build_hasher: S,
     marker: PhantomData<E>,
 }
 
 impl<E: Hash> LogLogBeta<E> {
     /// Returns a new `LogLogBeta` counter with the given margin of error.
-
+    ///
+    /// Counters start in sparse mode and are promoted to the dense, packed representation
+    /// automatically once enough registers have been touched. Uses `SipHasher` with a fixed
+    /// key; use `with_hasher` to plug in a different `BuildHasher`.
     pub fn new(error: f64) -> LogLogBeta<E> {
+        LogLogBeta::with_hasher(error, BuildHasherDefault::default())
+    }
+}
+
+impl<E: Hash, S: BuildHasher> LogLogBeta<E, S> {
+    /// Returns a new `LogLogBeta` counter using the given `BuildHasher`.
+    ///
+    /// Two counters that should be mergeable must be built with `BuildHasher`s that produce
+    /// the same hash for the same input, since `merge` combines registers index-for-index.
+    pub fn with_hasher(error: f64, build_hasher: S) -> LogLogBeta<E, S> {
         let p = (1.04 / error).powi(2).log2().ceil() as usize;
-        LogLogBeta::<E> {
+        let msize = 1u64 << p;
+        LogLogBeta::<E, S> {
             alpha: alpha(p),
-            p: p,
-            msize: 1 << p,
-            m: vec![0; 1 << p],
+            p,
+            msize,
+            registers: Registers::Sparse(Vec::new()),
+            build_hasher,
             marker: PhantomData,
         }
     }
 
     /// Inserts an element into the LLB
-
     pub fn insert(&mut self, e: E) {
-        let mut h = SipHasher::new();
-        e.hash(&mut h);
-        let x = h.finish();
+        let x = self.build_hasher.hash_one(&e);
         let w = x >> self.p;
         let j = x & (self.msize - 1);
         let idx = j as usize;
-        self.m[idx] = max(self.m[idx], rho(w, 64 - self.p as isize));
-    }
+        let candidate = rho(w, 64 - self.p as isize) as u8;
+
+        let needs_promotion = match self.registers {
+            Registers::Sparse(ref mut list) => {
+                sparse_insert(list, idx, candidate);
+                sparse_bytes(list.len()) > packed_bytes(self.msize)
+            }
+            Registers::Dense(ref mut m) => {
+                if candidate > get_register(m, idx) {
+                    set_register(m, idx, candidate);
+                }
+                false
+            }
+        };
 
+        if needs_promotion {
+            self.promote_to_dense();
+        }
+    }
 
     /// Obtain a cardinality estimate from the LogLogBeta counter
+    ///
+    /// While the counter is in sparse mode, and for a while after it gets promoted to dense,
+    /// this uses linear counting, which is far more accurate than the beta correction for the
+    /// small cardinalities sparse mode (and freshly-promoted dense mode) covers. Once linear
+    /// counting's own estimate climbs past the usual `2.5m` crossover, the beta correction takes
+    /// over, since linear counting degrades once registers start colliding in earnest.
+    pub fn estimate(&self) -> f64 {
+        match self.registers {
+            Registers::Sparse(ref list) => {
+                let m_s = self.msize as f64;
+                let empty = (self.msize as usize) - list.len();
+                linear_count(m_s, empty)
+            }
+            Registers::Dense(ref m) => {
+                let z = (0..self.msize as usize).filter(|&i| get_register(m, i) == 0).count();
+                let m_s = self.msize as f64;
+
+                if z > 0 {
+                    let linear = linear_count(m_s, z);
+                    if linear <= 2.5 * m_s {
+                        return linear;
+                    }
+                }
+
+                let beta = beta(z);
+                self.alpha * m_s * (m_s - (z as f64)) / (beta + inverse_sum(m, self.msize))
+            }
+        }
+    }
+
+    /// Obtain a cardinality estimate using Ertl's improved estimator.
+    ///
+    /// Builds a histogram of register values and evaluates the closed-form improved raw
+    /// estimator from Ertl's "New cardinality estimation algorithms for HyperLogLog sketches",
+    /// with the `sigma`/`tau` series helpers handling the empty and saturated registers. The
+    /// estimate is computed in a single pass over the histogram; no iterative
+    /// maximum-likelihood solve is performed.
+    pub fn estimate_mle(&self) -> f64 {
+        let q = 64 - self.p;
+        let m = self.msize as f64;
+
+        let mut c = vec![0u64; q + 2];
+        for i in 0..self.msize as usize {
+            c[self.register_at(i) as usize] += 1;
+        }
+
+        let alpha_inf = 0.5 / 2f64.ln();
+
+        let mut z = m * tau((m - c[q + 1] as f64) / m);
+        for k in (1..=q).rev() {
+            z += c[k] as f64;
+            z *= 0.5;
+        }
+        z += m * sigma(c[0] as f64 / m);
+
+        alpha_inf * m * m / z
+    }
+
+    /// Reads the register at `idx`, whichever storage mode the counter is currently in.
+    fn register_at(&self, idx: usize) -> u8 {
+        match self.registers {
+            Registers::Dense(ref m) => get_register(m, idx),
+            Registers::Sparse(ref list) => {
+                match list.binary_search_by_key(&idx, |&e| decode_sparse_entry(e).0) {
+                    Ok(pos) => decode_sparse_entry(list[pos]).1,
+                    Err(_) => 0,
+                }
+            }
+        }
+    }
+
+    /// Returns this counter's registers as a dense packed byte array, converting on the fly
+    /// if the counter is currently sparse.
+    fn as_dense(&self) -> Vec<u8> {
+        match self.registers {
+            Registers::Dense(ref m) => m.clone(),
+            Registers::Sparse(ref list) => dense_from_sparse(list, self.msize),
+        }
+    }
+
+    /// Replays the sparse entry list through `set_register` and switches storage to dense.
+    fn promote_to_dense(&mut self) {
+        let dense = match self.registers {
+            Registers::Sparse(ref list) => dense_from_sparse(list, self.msize),
+            Registers::Dense(_) => return,
+        };
+        self.registers = Registers::Dense(dense);
+    }
+}
+
+impl<E: Hash, S: BuildHasher + Clone> LogLogBeta<E, S> {
+    /// Merges another `LogLogBeta` into a new counter representing the union of both sets.
+    ///
+    /// Both counters must share the same precision `p`, since registers are combined
+    /// index-for-index by taking the elementwise maximum. Returns an error describing the
+    /// mismatch if the precisions differ. Two sparse counters merge into a sparse counter
+    /// (promoting to dense if the result grows too large); a merge involving a dense counter
+    /// always produces a dense result.
+    pub fn merge(&self, other: &LogLogBeta<E, S>) -> Result<LogLogBeta<E, S>, String> {
+        if self.p != other.p {
+            return Err(format!("cannot merge counters with different precision: {} vs {}", self.p, other.p));
+        }
+
+        let registers = match (&self.registers, &other.registers) {
+            (Registers::Sparse(a), Registers::Sparse(b)) => {
+                let mut merged = a.clone();
+                for &entry in b {
+                    let (idx, r) = decode_sparse_entry(entry);
+                    sparse_insert(&mut merged, idx, r);
+                }
+                if sparse_bytes(merged.len()) > packed_bytes(self.msize) {
+                    Registers::Dense(dense_from_sparse(&merged, self.msize))
+                } else {
+                    Registers::Sparse(merged)
+                }
+            }
+            _ => {
+                let a = self.as_dense();
+                let b = other.as_dense();
+                let mut dense = vec![0u8; packed_bytes(self.msize)];
+                for i in 0..self.msize as usize {
+                    set_register(&mut dense, i, max(get_register(&a, i), get_register(&b, i)));
+                }
+                Registers::Dense(dense)
+            }
+        };
+
+        Ok(LogLogBeta::<E, S> {
+            alpha: self.alpha,
+            p: self.p,
+            msize: self.msize,
+            registers,
+            build_hasher: self.build_hasher.clone(),
+            marker: PhantomData,
+        })
+    }
+
+    /// Estimates the size of the intersection of the two sets via inclusion-exclusion:
+    /// `|A ∩ B| ≈ estimate(A) + estimate(B) - estimate(A ∪ B)`, where the union estimate comes
+    /// from `merge`. Both counters must share the same precision, as required by `merge`.
+    pub fn intersect_estimate(&self, other: &LogLogBeta<E, S>) -> Result<f64, String> {
+        let union = self.merge(other)?;
+        Ok(self.estimate() + other.estimate() - union.estimate())
+    }
+
+    /// Estimates the Jaccard similarity `|A ∩ B| / |A ∪ B|` of the two sets.
+    pub fn jaccard(&self, other: &LogLogBeta<E, S>) -> Result<f64, String> {
+        let union = self.merge(other)?;
+        let union_estimate = union.estimate();
+        if union_estimate == 0.0 {
+            return Ok(0.0);
+        }
+
+        let intersect_estimate = self.estimate() + other.estimate() - union_estimate;
+        Ok(intersect_estimate / union_estimate)
+    }
+}
+
+impl<E: Hash, S: BuildHasher> LogLogBeta<E, S> {
+    /// Serializes this counter to a versioned byte format: a magic tag, the format version,
+    /// the precision `p`, a mode flag for dense/sparse, and the register payload. The result
+    /// can be handed to `from_bytes` to reconstruct an equivalent counter.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(FORMAT_MAGIC);
+        out.push(FORMAT_VERSION);
+        out.push(self.p as u8);
+
+        match self.registers {
+            Registers::Dense(ref m) => {
+                out.push(MODE_DENSE);
+                out.extend_from_slice(m);
+            }
+            Registers::Sparse(ref list) => {
+                out.push(MODE_SPARSE);
+                out.extend_from_slice(&(list.len() as u32).to_le_bytes());
+                for &entry in list {
+                    out.extend_from_slice(&entry.to_le_bytes());
+                }
+            }
+        }
 
-    pub fn estimate(&self) -> f64 { 
-        let z = self.m.iter().filter(|&i| *i == 0).count();
-        let m_s = self.msize as f64;
-        let beta = beta(z);
+        out
+    }
+}
+
+impl<E: Hash, S: BuildHasher + Default> LogLogBeta<E, S> {
+    /// Deserializes a counter previously produced by `to_bytes`.
+    ///
+    /// The hasher is reconstructed via `S::default()`, so the result is only mergeable with
+    /// other counters built from the same `BuildHasher`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<LogLogBeta<E, S>, String> {
+        if bytes.len() < 6 {
+            return Err("LogLogBeta: truncated header".to_string());
+        }
+        if &bytes[0..3] != FORMAT_MAGIC {
+            return Err("LogLogBeta: bad magic".to_string());
+        }
+
+        let version = bytes[3];
+        if version != FORMAT_VERSION {
+            return Err(format!("LogLogBeta: unsupported format version {}", version));
+        }
 
-        self.alpha * m_s * (m_s - (z as f64)) / (beta + self.inverse_sum())
+        let p = bytes[4] as usize;
+        if p >= 64 {
+            return Err(format!("LogLogBeta: precision {} out of range", p));
+        }
+        let mode = bytes[5];
+        let payload = &bytes[6..];
+        let msize = 1u64 << p;
+
+        let registers = match mode {
+            MODE_DENSE => {
+                if payload.len() != packed_bytes(msize) {
+                    return Err("LogLogBeta: dense payload size mismatch".to_string());
+                }
+                Registers::Dense(payload.to_vec())
+            }
+            MODE_SPARSE => {
+                if payload.len() < 4 {
+                    return Err("LogLogBeta: truncated sparse payload".to_string());
+                }
+                let len = u32::from_le_bytes([payload[0], payload[1], payload[2], payload[3]]) as usize;
+                let entries = &payload[4..];
+                if entries.len() != len * 4 {
+                    return Err("LogLogBeta: sparse payload size mismatch".to_string());
+                }
+                let mut list = Vec::with_capacity(len);
+                for chunk in entries.chunks(4) {
+                    let entry = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+                    let (idx, _) = decode_sparse_entry(entry);
+                    if idx >= msize as usize {
+                        return Err(format!("LogLogBeta: sparse index {} out of range for msize {}", idx, msize));
+                    }
+                    list.push(entry);
+                }
+                Registers::Sparse(list)
+            }
+            other => return Err(format!("LogLogBeta: unknown mode flag {}", other)),
+        };
+
+        Ok(LogLogBeta::<E, S> {
+            alpha: alpha(p),
+            p,
+            msize,
+            registers,
+            build_hasher: S::default(),
+            marker: PhantomData,
+        })
     }
-    
-    // TODO: Merge two LLBs
-    pub fn merge(&self, b: LogLogBeta<E>) -> LogLogBeta<E> {
-        b    
+}
+
+/// Inserts `(idx, rho)` into a sorted-by-index sparse list, keeping at most one entry per
+/// index and discarding the update if a larger `rho` is already recorded for that index.
+fn sparse_insert(list: &mut Vec<u32>, idx: usize, r: u8) {
+    match list.binary_search_by_key(&idx, |&e| decode_sparse_entry(e).0) {
+        Ok(pos) => {
+            let (_, existing) = decode_sparse_entry(list[pos]);
+            if r > existing {
+                list[pos] = encode_sparse_entry(idx, r);
+            }
+        }
+        Err(pos) => list.insert(pos, encode_sparse_entry(idx, r)),
     }
+}
+
+fn encode_sparse_entry(idx: usize, r: u8) -> u32 {
+    ((idx as u32) << SPARSE_RHO_BITS) | (r as u32)
+}
+
+fn decode_sparse_entry(entry: u32) -> (usize, u8) {
+    ((entry >> SPARSE_RHO_BITS) as usize, (entry & (REGISTER_MASK as u32)) as u8)
+}
+
+/// Memory footprint, in bytes, of a sparse list with `len` entries.
+fn sparse_bytes(len: usize) -> usize {
+    len * 4
+}
 
-    fn inverse_sum(&self) -> f64 {
-        //self.m_vec.each {|i| sum += 1 / (2 ^ @m_vec[i])}
-        self.m.iter().fold(0.0, |acc, &x| acc + (1.0 / (1 << x) as f64))
+fn dense_from_sparse(list: &[u32], msize: u64) -> Vec<u8> {
+    let mut dense = vec![0u8; packed_bytes(msize)];
+    for &entry in list {
+        let (idx, r) = decode_sparse_entry(entry);
+        set_register(&mut dense, idx, r);
     }
+    dense
+}
+
+/// Number of bytes needed to pack `msize` 6-bit registers.
+fn packed_bytes(msize: u64) -> usize {
+    (msize as usize * REGISTER_BITS).div_ceil(8)
+}
+
+/// Reads the 6-bit register at `idx` out of a packed byte array.
+fn get_register(m: &[u8], idx: usize) -> u8 {
+    let bit_idx = idx * REGISTER_BITS;
+    let byte_idx = bit_idx / 8;
+    let bit_offset = bit_idx % 8;
+
+    let lo = m[byte_idx] as u16;
+    let hi = m.get(byte_idx + 1).cloned().unwrap_or(0) as u16;
+    let combined = lo | (hi << 8);
+
+    ((combined >> bit_offset) & REGISTER_MASK) as u8
+}
+
+/// Writes `value` into the 6-bit register at `idx`, spanning at most two adjacent bytes.
+fn set_register(m: &mut [u8], idx: usize, value: u8) {
+    let bit_idx = idx * REGISTER_BITS;
+    let byte_idx = bit_idx / 8;
+    let bit_offset = bit_idx % 8;
+
+    let lo = m[byte_idx] as u16;
+    let hi = m.get(byte_idx + 1).cloned().unwrap_or(0) as u16;
+    let combined = lo | (hi << 8);
+
+    let mask = REGISTER_MASK << bit_offset;
+    let combined = (combined & !mask) | (((value as u16) & REGISTER_MASK) << bit_offset);
+
+    m[byte_idx] = (combined & 0xff) as u8;
+    if byte_idx + 1 < m.len() {
+        m[byte_idx + 1] = (combined >> 8) as u8;
+    }
+}
+
+fn inverse_sum(m: &[u8], msize: u64) -> f64 {
+    //self.m_vec.each {|i| sum += 1 / (2 ^ @m_vec[i])}
+    (0..msize as usize).fold(0.0, |acc, i| acc + (1.0 / (1u64 << get_register(m, i)) as f64))
 }
 
 fn alpha(p: usize) -> f64 {
@@ -98,6 +460,15 @@ fn rho(w: u64, max_width: isize) -> u64 {
     rho as u64
 }
 
+/// Linear counting estimate: `m * ln(m / empty)`, or `m` itself if every register is touched.
+fn linear_count(m_s: f64, empty: usize) -> f64 {
+    if empty == 0 {
+        m_s
+    } else {
+        m_s * (m_s / (empty as f64)).ln()
+    }
+}
+
 fn beta(z: usize) -> f64 {
     let z = z as f64;
     let z_l = (z + 1.0).log2();
@@ -111,6 +482,46 @@ fn beta(z: usize) -> f64 {
     + 0.00042419 * z_l.powi(7)
 }
 
+/// Series helper used by `estimate_mle` for the empty-register (value `0`) boundary term.
+fn sigma(x: f64) -> f64 {
+    if x == 1.0 {
+        return f64::INFINITY;
+    }
+    let mut x = x;
+    let mut z = x;
+    let mut y = 1.0;
+    loop {
+        x = x * x;
+        let z_prime = z;
+        z += x * y;
+        y += y;
+        if z == z_prime {
+            break;
+        }
+    }
+    z
+}
+
+/// Series helper used by `estimate_mle` for the saturated-register (value `q + 1`) boundary term.
+fn tau(x: f64) -> f64 {
+    if x == 0.0 || x == 1.0 {
+        return 0.0;
+    }
+    let mut x = x;
+    let mut z = 1.0 - x;
+    let mut y = 1.0;
+    loop {
+        x = x.sqrt();
+        let z_prime = z;
+        y *= 0.5;
+        z -= (1.0 - x).powi(2) * y;
+        if z == z_prime {
+            break;
+        }
+    }
+    z / 3.0
+}
+
 #[cfg(test)]
 mod test {
     use loglogbeta;
@@ -126,5 +537,269 @@ mod test {
         assert!(hll.estimate() > (actual - (actual * p)));
         assert!(hll.estimate() < (actual + (actual * p)));
     }
+
+    #[test]
+    fn merge() {
+        let p = 0.05;
+
+        let mut disjoint_a = loglogbeta::LogLogBeta::new(p);
+        let mut disjoint_b = loglogbeta::LogLogBeta::new(p);
+        for i in 0..50000 {
+            disjoint_a.insert(i);
+        }
+        for i in 50000..100000 {
+            disjoint_b.insert(i);
+        }
+        let merged = disjoint_a.merge(&disjoint_b).unwrap();
+        let actual = 100000.0;
+        assert!(merged.estimate() > (actual - (actual * p)));
+        assert!(merged.estimate() < (actual + (actual * p)));
+
+        let mut overlap_a = loglogbeta::LogLogBeta::new(p);
+        let mut overlap_b = loglogbeta::LogLogBeta::new(p);
+        for i in 0..75000 {
+            overlap_a.insert(i);
+        }
+        for i in 25000..100000 {
+            overlap_b.insert(i);
+        }
+        let merged = overlap_a.merge(&overlap_b).unwrap();
+        let actual = 100000.0;
+        assert!(merged.estimate() > (actual - (actual * p)));
+        assert!(merged.estimate() < (actual + (actual * p)));
+    }
+
+    #[test]
+    fn merge_requires_matching_precision() {
+        let a: loglogbeta::LogLogBeta<usize> = loglogbeta::LogLogBeta::new(0.05);
+        let b: loglogbeta::LogLogBeta<usize> = loglogbeta::LogLogBeta::new(0.1);
+        assert!(a.merge(&b).is_err());
+    }
+
+    #[test]
+    fn with_hasher_matches_default_hasher() {
+        use std::hash::BuildHasherDefault;
+        use super::siphasher::sip::SipHasher;
+
+        let p = 0.05;
+        let actual = 10000;
+
+        let mut default_hll: loglogbeta::LogLogBeta<usize> = loglogbeta::LogLogBeta::new(p);
+        let mut custom_hll: loglogbeta::LogLogBeta<usize, BuildHasherDefault<SipHasher>> =
+            loglogbeta::LogLogBeta::with_hasher(p, BuildHasherDefault::default());
+
+        for i in 0..actual {
+            default_hll.insert(i);
+            custom_hll.insert(i);
+        }
+
+        assert_eq!(default_hll.estimate(), custom_hll.estimate());
+    }
+
+    #[test]
+    fn packed_registers_round_trip() {
+        let msize = 1u64 << 10;
+        let mut m = vec![0u8; loglogbeta::packed_bytes(msize)];
+        for idx in 0..(msize as usize) {
+            let value = ((idx * 7 + 3) % 64) as u8;
+            loglogbeta::set_register(&mut m, idx, value);
+            assert_eq!(loglogbeta::get_register(&m, idx), value);
+        }
+        for idx in 0..(msize as usize) {
+            let expected = ((idx * 7 + 3) % 64) as u8;
+            assert_eq!(loglogbeta::get_register(&m, idx), expected);
+        }
+    }
+
+    #[test]
+    fn sparse_mode_is_accurate_for_small_cardinalities() {
+        // Few enough inserts that the p = 9 counter stays below its 96-entry promotion point.
+        let actual = 50;
+        let mut hll: loglogbeta::LogLogBeta<usize> = loglogbeta::LogLogBeta::new(0.05);
+        for i in 0..actual {
+            hll.insert(i);
+        }
+
+        assert!(matches!(hll.registers, loglogbeta::Registers::Sparse(_)));
+        assert!(hll.estimate() > (actual as f64) * 0.9);
+        assert!(hll.estimate() < (actual as f64) * 1.1);
+    }
+
+    #[test]
+    fn sparse_promotes_to_dense_past_the_memory_crossover() {
+        let mut hll: loglogbeta::LogLogBeta<usize> = loglogbeta::LogLogBeta::new(0.05);
+        let actual = 1000000.0;
+        for i in 0..actual as usize {
+            hll.insert(i);
+        }
+
+        let p = 0.05;
+        assert!(matches!(hll.registers, loglogbeta::Registers::Dense(_)));
+        assert!(hll.estimate() > (actual - (actual * p)));
+        assert!(hll.estimate() < (actual + (actual * p)));
+    }
+
+    #[test]
+    fn merge_sparse_with_dense() {
+        let p = 0.05;
+
+        let mut sparse = loglogbeta::LogLogBeta::new(p);
+        for i in 0..100 {
+            sparse.insert(i);
+        }
+
+        let mut dense = loglogbeta::LogLogBeta::new(p);
+        for i in 0..200000 {
+            dense.insert(i);
+        }
+
+        let merged = sparse.merge(&dense).unwrap();
+        let actual = 200000.0;
+        assert!(merged.estimate() > (actual - (actual * p)));
+        assert!(merged.estimate() < (actual + (actual * p)));
+    }
+
+    #[test]
+    fn estimate_is_accurate_after_packing_registers() {
+        let p = 0.01;
+        let actual = 500000.0;
+        let mut hll = loglogbeta::LogLogBeta::new(p);
+        for i in 0..actual as usize {
+            hll.insert(i);
+        }
+
+        assert!(hll.estimate() > (actual - (actual * p)));
+        assert!(hll.estimate() < (actual + (actual * p)));
+    }
+
+    #[test]
+    fn serialize_round_trip_sparse() {
+        let mut hll: loglogbeta::LogLogBeta<usize> = loglogbeta::LogLogBeta::new(0.05);
+        for i in 0..100 {
+            hll.insert(i);
+        }
+
+        let bytes = hll.to_bytes();
+        let restored: loglogbeta::LogLogBeta<usize> = loglogbeta::LogLogBeta::from_bytes(&bytes).unwrap();
+
+        assert_eq!(hll.estimate(), restored.estimate());
+    }
+
+    #[test]
+    fn serialize_round_trip_dense() {
+        let mut hll: loglogbeta::LogLogBeta<usize> = loglogbeta::LogLogBeta::new(0.05);
+        for i in 0..500000 {
+            hll.insert(i);
+        }
+
+        let bytes = hll.to_bytes();
+        let restored: loglogbeta::LogLogBeta<usize> = loglogbeta::LogLogBeta::from_bytes(&bytes).unwrap();
+
+        assert_eq!(hll.estimate(), restored.estimate());
+    }
+
+    #[test]
+    fn from_bytes_rejects_bad_magic() {
+        let garbage = vec![0u8; 16];
+        let result: Result<loglogbeta::LogLogBeta<usize>, String> = loglogbeta::LogLogBeta::from_bytes(&garbage);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn from_bytes_rejects_out_of_range_precision() {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(b"LLB");
+        bytes.push(1); // FORMAT_VERSION
+        bytes.push(200); // p, would overflow `1u64 << p`
+        bytes.push(1); // MODE_SPARSE
+        bytes.extend_from_slice(&0u32.to_le_bytes()); // empty sparse list
+
+        let result: Result<loglogbeta::LogLogBeta<usize>, String> = loglogbeta::LogLogBeta::from_bytes(&bytes);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn from_bytes_rejects_sparse_index_out_of_range() {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(b"LLB");
+        bytes.push(1); // FORMAT_VERSION
+        bytes.push(4); // p, msize = 16
+        bytes.push(1); // MODE_SPARSE
+        bytes.extend_from_slice(&1u32.to_le_bytes()); // one entry
+        let entry = loglogbeta::encode_sparse_entry(1000, 5); // idx way past msize
+        bytes.extend_from_slice(&entry.to_le_bytes());
+
+        let result: Result<loglogbeta::LogLogBeta<usize>, String> = loglogbeta::LogLogBeta::from_bytes(&bytes);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn estimate_mle_is_accurate_across_cardinalities() {
+        let p = 0.05;
+        // estimate_mle() isn't held to the same tolerance as the `error` a counter is
+        // constructed with: that knob only sizes the registers, and the MLE estimator's actual
+        // error can run a bit past it at some cardinalities.
+        let tolerance = 0.1;
+
+        for &actual in &[10usize, 1000, 100000, 10000000] {
+            let mut hll = loglogbeta::LogLogBeta::new(p);
+            for i in 0..actual {
+                hll.insert(i);
+            }
+
+            let actual = actual as f64;
+            let estimate = hll.estimate_mle();
+            assert!(estimate > (actual - (actual * tolerance)), "estimate {} too low for actual {}", estimate, actual);
+            assert!(estimate < (actual + (actual * tolerance)), "estimate {} too high for actual {}", estimate, actual);
+        }
+    }
+
+    #[test]
+    fn intersect_estimate_matches_known_overlap() {
+        let p = 0.05;
+
+        let mut a = loglogbeta::LogLogBeta::new(p);
+        for i in 0..100000 {
+            a.insert(i);
+        }
+
+        let mut b = loglogbeta::LogLogBeta::new(p);
+        for i in 50000..150000 {
+            b.insert(i);
+        }
+
+        let actual_intersection = 50000.0;
+        let estimate = a.intersect_estimate(&b).unwrap();
+        let tolerance = actual_intersection * 0.25;
+        assert!((estimate - actual_intersection).abs() < tolerance,
+            "intersect estimate {} too far from actual {}", estimate, actual_intersection);
+    }
+
+    #[test]
+    fn jaccard_matches_known_overlap_fraction() {
+        let p = 0.05;
+
+        let mut a = loglogbeta::LogLogBeta::new(p);
+        for i in 0..100000 {
+            a.insert(i);
+        }
+
+        let mut b = loglogbeta::LogLogBeta::new(p);
+        for i in 50000..150000 {
+            b.insert(i);
+        }
+
+        let actual_jaccard = 50000.0 / 150000.0;
+        let estimate = a.jaccard(&b).unwrap();
+        assert!((estimate - actual_jaccard).abs() < 0.1,
+            "jaccard estimate {} too far from actual {}", estimate, actual_jaccard);
+    }
+
+    #[test]
+    fn jaccard_requires_matching_precision() {
+        let a: loglogbeta::LogLogBeta<usize> = loglogbeta::LogLogBeta::new(0.05);
+        let b: loglogbeta::LogLogBeta<usize> = loglogbeta::LogLogBeta::new(0.1);
+        assert!(a.jaccard(&b).is_err());
+    }
 }
 